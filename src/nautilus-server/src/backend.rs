@@ -0,0 +1,140 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `TransformerBackend` trait decouples the signed-response wrapper in
+//! `gemini.rs` (base64 decode, `file_hash`, answer cleaning, timestamping,
+//! `to_signed_response`) from any one LLM provider, so enclave operators can
+//! offer provider choice, or fail over between providers, without the
+//! attestation/signing path diverging per provider.
+
+use crate::gemini::ConversationTurn;
+use crate::EnclaveError;
+use serde::{Deserialize, Serialize};
+
+/// Server-side ceilings so a request can't push sampling parameters past
+/// what the operator is willing to pay for.
+pub const MAX_TEMPERATURE: f32 = 1.0;
+pub const MAX_OUTPUT_TOKENS_CEILING: u32 = 8192;
+pub const MAX_TOP_P: f32 = 1.0;
+pub const MAX_TOP_K: u32 = 40;
+
+/// A Gemini safety category and the minimum severity to block at.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+/// Gemini's block thresholds, ordered from weakest to strictest.
+const SAFETY_THRESHOLD_RANKS: &[&str] = &[
+    "BLOCK_NONE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_LOW_AND_ABOVE",
+];
+
+/// The weakest threshold a request is allowed to set; a caller can ask for
+/// something stricter, but can't weaken the operator's safety posture on the
+/// operator's own key.
+const MIN_SAFETY_THRESHOLD: &str = "BLOCK_ONLY_HIGH";
+
+fn safety_threshold_rank(threshold: &str) -> usize {
+    SAFETY_THRESHOLD_RANKS
+        .iter()
+        .position(|&t| t == threshold)
+        .unwrap_or(0)
+}
+
+/// Raises any caller-supplied threshold that's weaker than
+/// `MIN_SAFETY_THRESHOLD` (including unrecognized values) up to the floor.
+pub fn clamp_safety_settings(settings: Vec<SafetySetting>) -> Vec<SafetySetting> {
+    let floor_rank = safety_threshold_rank(MIN_SAFETY_THRESHOLD);
+    settings
+        .into_iter()
+        .map(|setting| {
+            if safety_threshold_rank(&setting.threshold) < floor_rank {
+                SafetySetting {
+                    category: setting.category,
+                    threshold: MIN_SAFETY_THRESHOLD.to_string(),
+                }
+            } else {
+                setting
+            }
+        })
+        .collect()
+}
+
+/// Sampling/safety parameters, already clamped to the ceilings above, that
+/// get echoed into the signed `GeminiResponse` so the attestation commits to
+/// exactly how the answer was generated. `top_k` and `safety_settings` are
+/// optional because not every backend accepts them: a `TransformerBackend`
+/// reports back only the subset it actually sent upstream, rather than the
+/// full clamped set it was handed, so the signed response never claims a
+/// parameter was applied when the provider never saw it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationParams {
+    pub temperature: f32,
+    pub max_output_tokens: u32,
+    pub top_p: f32,
+    pub top_k: Option<u32>,
+    pub safety_settings: Option<Vec<SafetySetting>>,
+}
+
+/// Normalized request handed to any `TransformerBackend`, independent of the
+/// wire format of the provider it's forwarded to.
+pub struct BackendRequest {
+    pub question: String,
+    pub file_content: String, // Base64 encoded file content
+    pub file_type: String,    // mime type
+    pub history: Vec<ConversationTurn>,
+    pub system_instruction: Option<String>,
+    /// Caller-chosen model override; backends fall back to their own
+    /// default when this is `None`.
+    pub model: Option<String>,
+    pub generation: GenerationParams,
+}
+
+/// What a backend produced, before the shared cleaning/signing logic in
+/// `gemini.rs` turns it into a `GeminiResponse`.
+pub struct BackendAnswer {
+    pub answer: String,
+    pub model: String,
+    /// The subset of `BackendRequest::generation` actually forwarded to the
+    /// provider, so the signed response never commits to a parameter the
+    /// backend silently dropped.
+    pub generation_config: GenerationParams,
+}
+
+/// Coarse classification of a file's mime type. Gemini accepts any binary
+/// mime type as `inlineData`, but OpenAI/Anthropic-compatible APIs only
+/// support specific multimodal content blocks, so those backends need to
+/// know which block (if any) a given file maps to rather than splicing its
+/// base64 into a plain text message.
+pub enum MimeCategory {
+    Text,
+    Image,
+    Pdf,
+    Unsupported,
+}
+
+pub fn classify_mime(mime_type: &str) -> MimeCategory {
+    if mime_type.starts_with("text/")
+        || matches!(
+            mime_type,
+            "application/json" | "application/xml" | "application/javascript"
+        )
+    {
+        MimeCategory::Text
+    } else if mime_type.starts_with("image/") {
+        MimeCategory::Image
+    } else if mime_type == "application/pdf" {
+        MimeCategory::Pdf
+    } else {
+        MimeCategory::Unsupported
+    }
+}
+
+#[async_trait::async_trait]
+pub trait TransformerBackend: Send + Sync {
+    async fn generate(&self, request: &BackendRequest) -> Result<BackendAnswer, EnclaveError>;
+}
@@ -0,0 +1,128 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::backend::{
+    classify_mime, BackendAnswer, BackendRequest, GenerationParams, MimeCategory, TransformerBackend,
+};
+use crate::AppState;
+use crate::EnclaveError;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_MODEL: &str = "claude-3-5-sonnet-latest";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Calls an Anthropic-compatible `messages` API.
+pub struct AnthropicBackend {
+    pub state: Arc<AppState>,
+}
+
+#[async_trait::async_trait]
+impl TransformerBackend for AnthropicBackend {
+    async fn generate(&self, request: &BackendRequest) -> Result<BackendAnswer, EnclaveError> {
+        let api_key = self.state.anthropic_api_key.as_ref().ok_or_else(|| {
+            EnclaveError::GenericError("Anthropic backend is not configured".to_string())
+        })?;
+        let model = request.model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+        let mut messages: Vec<Value> = request
+            .history
+            .iter()
+            .map(|turn| {
+                let role = if turn.role == "model" { "assistant" } else { "user" };
+                serde_json::json!({ "role": role, "content": turn.text })
+            })
+            .collect();
+        // Anthropic's `messages` API covers text, images, and PDFs as
+        // document blocks; anything else can't be represented without
+        // silently corrupting it, so we reject it instead.
+        let user_content: Value = match classify_mime(&request.file_type) {
+            MimeCategory::Text => {
+                let file_bytes = general_purpose::STANDARD.decode(&request.file_content).map_err(|e| {
+                    EnclaveError::GenericError(format!("Failed to decode file content: {}", e))
+                })?;
+                serde_json::json!(format!(
+                    "Analyze the attached {} file and answer this question: {}\n\nFile content:\n{}",
+                    request.file_type,
+                    request.question,
+                    String::from_utf8_lossy(&file_bytes)
+                ))
+            }
+            MimeCategory::Image => serde_json::json!([
+                {
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": request.file_type, "data": request.file_content }
+                },
+                { "type": "text", "text": request.question }
+            ]),
+            MimeCategory::Pdf => serde_json::json!([
+                {
+                    "type": "document",
+                    "source": { "type": "base64", "media_type": "application/pdf", "data": request.file_content }
+                },
+                { "type": "text", "text": request.question }
+            ]),
+            MimeCategory::Unsupported => {
+                return Err(EnclaveError::GenericError(format!(
+                    "Anthropic backend does not support {} files; use the gemini provider instead",
+                    request.file_type
+                )));
+            }
+        };
+        messages.push(serde_json::json!({ "role": "user", "content": user_content }));
+
+        let mut body = serde_json::json!({
+            "model": model,
+            "max_tokens": request.generation.max_output_tokens,
+            "messages": messages,
+            "temperature": request.generation.temperature,
+            "top_p": request.generation.top_p,
+        });
+        if let Some(system_instruction) = &request.system_instruction {
+            body["system"] = serde_json::json!(system_instruction);
+        }
+        // Unlike OpenAI, Anthropic's `messages` API does accept `top_k`.
+        if let Some(top_k) = request.generation.top_k {
+            body["top_k"] = serde_json::json!(top_k);
+        }
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("Content-Type", "application/json")
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to call Anthropic API: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EnclaveError::GenericError(format!("Anthropic API error: {}", error_text)));
+        }
+
+        let json = response
+            .json::<Value>()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to parse Anthropic response: {}", e)))?;
+
+        let answer = json["content"][0]["text"]
+            .as_str()
+            .unwrap_or("No response generated")
+            .to_string();
+
+        // Anthropic has no concept of Gemini-style safety settings, so those
+        // were never sent; `top_k` was (see above), so it's echoed back.
+        let generation_config = GenerationParams {
+            temperature: request.generation.temperature,
+            max_output_tokens: request.generation.max_output_tokens,
+            top_p: request.generation.top_p,
+            top_k: request.generation.top_k,
+            safety_settings: None,
+        };
+
+        Ok(BackendAnswer { answer, model, generation_config })
+    }
+}
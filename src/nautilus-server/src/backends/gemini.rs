@@ -0,0 +1,156 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::backend::{BackendAnswer, BackendRequest, GenerationParams, TransformerBackend};
+use crate::AppState;
+use crate::EnclaveError;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Mime types Gemini is happy to receive as a plain text part rather than
+/// `inlineData`. Everything else (images, PDFs, audio, ...) is shipped as
+/// base64 `inlineData` so the original bytes reach the model untouched.
+fn is_text_mime(mime_type: &str) -> bool {
+    mime_type.starts_with("text/")
+        || matches!(
+            mime_type,
+            "application/json" | "application/xml" | "application/javascript"
+        )
+}
+
+/// Calls Gemini directly, or Vertex AI when `state.vertex` is configured.
+pub struct GeminiBackend {
+    pub state: Arc<AppState>,
+}
+
+#[async_trait::async_trait]
+impl TransformerBackend for GeminiBackend {
+    async fn generate(&self, request: &BackendRequest) -> Result<BackendAnswer, EnclaveError> {
+        let file_bytes = general_purpose::STANDARD
+            .decode(&request.file_content)
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to decode file content: {}", e)))?;
+
+        let model = request.model.clone().unwrap_or_else(|| "gemini-1.5-flash".to_string());
+        let (url, auth_header) = if let Some(vertex) = &self.state.vertex {
+            let token = vertex.access_token().await?;
+            (
+                format!(
+                    "https://{location}-aiplatform.googleapis.com/v1/projects/{project}/locations/{location}/publishers/google/models/{model}:generateContent",
+                    location = vertex.config.location,
+                    project = vertex.config.project,
+                    model = model,
+                ),
+                Some(format!("Bearer {}", token)),
+            )
+        } else {
+            (
+                format!(
+                    "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+                    model, self.state.gemini_api_key
+                ),
+                None,
+            )
+        };
+
+        let prompt = format!(
+            "Analyze the attached {} file and answer this question: {}\n\nIMPORTANT: Provide a clear, concise answer without using any special characters, markdown formatting, asterisks, dollar signs, or newlines. Use only plain text with spaces.",
+            request.file_type, request.question,
+        );
+
+        // Gemini's multi-part content form: one part carries the question,
+        // the other carries the file, either inline as text or as base64
+        // `inlineData` depending on its mime type.
+        let file_part = if is_text_mime(&request.file_type) {
+            serde_json::json!({ "text": String::from_utf8_lossy(&file_bytes) })
+        } else {
+            serde_json::json!({
+                "inlineData": {
+                    "mimeType": request.file_type,
+                    "data": request.file_content
+                }
+            })
+        };
+
+        // Map prior turns into Gemini's `contents` array, then append the new
+        // question as the final user turn.
+        let mut contents: Vec<Value> = request
+            .history
+            .iter()
+            .map(|turn| {
+                serde_json::json!({
+                    "role": turn.role,
+                    "parts": [{ "text": turn.text }]
+                })
+            })
+            .collect();
+        contents.push(serde_json::json!({
+            "role": "user",
+            "parts": [
+                { "text": prompt },
+                file_part
+            ]
+        }));
+
+        let mut body = serde_json::json!({
+            "contents": contents,
+            "generationConfig": {
+                "temperature": request.generation.temperature,
+                "maxOutputTokens": request.generation.max_output_tokens,
+                "topP": request.generation.top_p,
+                "topK": request.generation.top_k,
+            }
+        });
+        if let Some(system_instruction) = &request.system_instruction {
+            body["systemInstruction"] = serde_json::json!({
+                "parts": [{ "text": system_instruction }]
+            });
+        }
+        if let Some(safety_settings) = &request.generation.safety_settings {
+            body["safetySettings"] = serde_json::json!(safety_settings
+                .iter()
+                .map(|s| serde_json::json!({ "category": s.category, "threshold": s.threshold }))
+                .collect::<Vec<_>>());
+        }
+
+        let client = reqwest::Client::new();
+        let mut request_builder = client
+            .post(&url)
+            .header("Content-Type", "application/json");
+        if let Some(auth_header) = auth_header {
+            request_builder = request_builder.header("Authorization", auth_header);
+        }
+        let response = request_builder
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to call Gemini API: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EnclaveError::GenericError(format!("Gemini API error: {}", error_text)));
+        }
+
+        let json = response
+            .json::<Value>()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to parse Gemini response: {}", e)))?;
+
+        let answer = json["candidates"][0]["content"]["parts"][0]["text"]
+            .as_str()
+            .unwrap_or("No response generated")
+            .to_string();
+
+        // Gemini is sent every field in `request.generation`, so the
+        // signed response can echo it back unchanged.
+        let generation_config = GenerationParams {
+            temperature: request.generation.temperature,
+            max_output_tokens: request.generation.max_output_tokens,
+            top_p: request.generation.top_p,
+            top_k: request.generation.top_k,
+            safety_settings: request.generation.safety_settings.clone(),
+        };
+
+        Ok(BackendAnswer { answer, model, generation_config })
+    }
+}
@@ -0,0 +1,6 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod anthropic;
+pub mod gemini;
+pub mod openai;
@@ -0,0 +1,113 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::backend::{
+    classify_mime, BackendAnswer, BackendRequest, GenerationParams, MimeCategory, TransformerBackend,
+};
+use crate::AppState;
+use crate::EnclaveError;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Calls an OpenAI-compatible `chat/completions` API.
+pub struct OpenAiBackend {
+    pub state: Arc<AppState>,
+}
+
+#[async_trait::async_trait]
+impl TransformerBackend for OpenAiBackend {
+    async fn generate(&self, request: &BackendRequest) -> Result<BackendAnswer, EnclaveError> {
+        let api_key = self.state.openai_api_key.as_ref().ok_or_else(|| {
+            EnclaveError::GenericError("OpenAI backend is not configured".to_string())
+        })?;
+        let model = request.model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+        let mut messages: Vec<Value> = Vec::new();
+        if let Some(system_instruction) = &request.system_instruction {
+            messages.push(serde_json::json!({ "role": "system", "content": system_instruction }));
+        }
+        for turn in &request.history {
+            let role = if turn.role == "model" { "assistant" } else { "user" };
+            messages.push(serde_json::json!({ "role": role, "content": turn.text }));
+        }
+        // OpenAI's chat/completions content blocks only cover text and
+        // images; anything else (PDF, audio, ...) can't be represented
+        // without silently corrupting it, so we reject it instead.
+        let user_content: Value = match classify_mime(&request.file_type) {
+            MimeCategory::Text => {
+                let file_bytes = general_purpose::STANDARD.decode(&request.file_content).map_err(|e| {
+                    EnclaveError::GenericError(format!("Failed to decode file content: {}", e))
+                })?;
+                serde_json::json!(format!(
+                    "Analyze the attached {} file and answer this question: {}\n\nFile content:\n{}",
+                    request.file_type,
+                    request.question,
+                    String::from_utf8_lossy(&file_bytes)
+                ))
+            }
+            MimeCategory::Image => serde_json::json!([
+                { "type": "text", "text": request.question },
+                {
+                    "type": "image_url",
+                    "image_url": { "url": format!("data:{};base64,{}", request.file_type, request.file_content) }
+                }
+            ]),
+            MimeCategory::Pdf | MimeCategory::Unsupported => {
+                return Err(EnclaveError::GenericError(format!(
+                    "OpenAI backend does not support {} files; use the gemini provider instead",
+                    request.file_type
+                )));
+            }
+        };
+        messages.push(serde_json::json!({ "role": "user", "content": user_content }));
+
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "temperature": request.generation.temperature,
+            "max_tokens": request.generation.max_output_tokens,
+            "top_p": request.generation.top_p,
+        });
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to call OpenAI API: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EnclaveError::GenericError(format!("OpenAI API error: {}", error_text)));
+        }
+
+        let json = response
+            .json::<Value>()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to parse OpenAI response: {}", e)))?;
+
+        let answer = json["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("No response generated")
+            .to_string();
+
+        // chat/completions has no `top_k` parameter and no concept of
+        // Gemini-style safety settings, so neither was sent upstream; the
+        // signed response must not claim otherwise.
+        let generation_config = GenerationParams {
+            temperature: request.generation.temperature,
+            max_output_tokens: request.generation.max_output_tokens,
+            top_p: request.generation.top_p,
+            top_k: None,
+            safety_settings: None,
+        };
+
+        Ok(BackendAnswer { answer, model, generation_config })
+    }
+}
@@ -1,6 +1,13 @@
 // Copyright (c), Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::backend::{
+    clamp_safety_settings, BackendRequest, GenerationParams, SafetySetting, TransformerBackend,
+    MAX_OUTPUT_TOKENS_CEILING, MAX_TEMPERATURE, MAX_TOP_K, MAX_TOP_P,
+};
+use crate::backends::anthropic::AnthropicBackend;
+use crate::backends::gemini::GeminiBackend;
+use crate::backends::openai::OpenAiBackend;
 use crate::common::IntentMessage;
 use crate::common::{to_signed_response, IntentScope, ProcessDataRequest, ProcessedDataResponse};
 use crate::AppState;
@@ -8,12 +15,15 @@ use crate::EnclaveError;
 use axum::extract::State;
 use axum::Json;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::sync::Arc;
 use sha2::{Sha256, Digest};
-use reqwest;
 use base64::{Engine as _, engine::general_purpose};
 
+/// Default ceiling on the decoded file size we'll ship to a backend,
+/// overridable via `GEMINI_MAX_FILE_BYTES` so enclave operators can tune it
+/// for their memory budget.
+const DEFAULT_MAX_FILE_BYTES: usize = 20 * 1024 * 1024;
+
 /// Inner type T for IntentMessage<T>
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeminiResponse {
@@ -21,6 +31,25 @@ pub struct GeminiResponse {
     pub answer: String,
     pub model: String,
     pub file_hash: Vec<u8>,
+    /// SHA-256 over the full conversation transcript (system instruction,
+    /// prior turns, and the new question) that produced `answer`, so a Sui
+    /// verifier can prove which context the signed answer came from.
+    pub transcript_hash: Vec<u8>,
+    /// The sampling/safety parameters actually sent to the chosen backend to
+    /// produce `answer` — not every provider accepts every field, so this is
+    /// a provider-specific subset of the clamped request, not the full
+    /// clamped set. The attestation commits to exactly how the answer was
+    /// produced, not just which model.
+    pub generation_config: GenerationParams,
+}
+
+/// One prior turn of a multi-turn conversation, mirroring Gemini's `contents`
+/// entries.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConversationTurn {
+    /// Either `"user"` or `"model"`.
+    pub role: String,
+    pub text: String,
 }
 
 /// Inner type T for ProcessDataRequest<T>
@@ -29,69 +58,146 @@ pub struct GeminiRequest {
     pub question: String,
     pub file_content: String, // Base64 encoded file content
     pub file_type: String,    // mime type
+    /// Prior turns of the conversation, oldest first. The new `question` is
+    /// appended as the final user turn.
+    pub history: Option<Vec<ConversationTurn>>,
+    /// Steers model behavior (e.g. "answer only in JSON"); mapped to
+    /// Gemini's top-level `systemInstruction`.
+    pub system_instruction: Option<String>,
+    /// Which backend answers the question: `"gemini"` (default), `"openai"`,
+    /// or `"anthropic"`.
+    pub provider: Option<String>,
+    /// Model override, e.g. `"gemini-1.5-pro"`; each backend has its own
+    /// default when this is omitted.
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_output_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub safety_settings: Option<Vec<SafetySetting>>,
+}
+
+fn max_file_bytes() -> usize {
+    std::env::var("GEMINI_MAX_FILE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_BYTES)
+}
+
+/// Feeds a length-prefixed field into a running hash so distinct field
+/// boundaries can't collide (e.g. turn `{role:"user", text:"ab"}` hashing
+/// the same as `{role:"us", text:"erab"}`).
+fn hash_field(hasher: &mut Sha256, field: &[u8]) {
+    hasher.update((field.len() as u64).to_le_bytes());
+    hasher.update(field);
+}
+
+/// Clamps the caller-supplied sampling knobs to operator-defined maxima and
+/// raises safety thresholds to the operator's floor before they're forwarded
+/// to a backend. This is the full set a backend is *allowed* to use
+/// (`safety_settings` is `None` when the caller supplied none); a backend reports
+/// back in `BackendAnswer::generation_config` only the subset it actually
+/// sent, which is what ends up in the signed response.
+fn effective_generation_params(request: &GeminiRequest) -> GenerationParams {
+    let safety_settings = clamp_safety_settings(request.safety_settings.clone().unwrap_or_default());
+    GenerationParams {
+        temperature: request.temperature.unwrap_or(0.7).clamp(0.0, MAX_TEMPERATURE),
+        max_output_tokens: request.max_output_tokens.unwrap_or(2048).min(MAX_OUTPUT_TOKENS_CEILING),
+        top_p: request.top_p.unwrap_or(1.0).clamp(0.0, MAX_TOP_P),
+        top_k: Some(request.top_k.unwrap_or(40).min(MAX_TOP_K)),
+        safety_settings: if safety_settings.is_empty() { None } else { Some(safety_settings) },
+    }
+}
+
+/// Picks the backend for a request's `provider` field, failing loudly on an
+/// unrecognized value instead of silently falling back to Gemini — a typo
+/// like `"claude"` would otherwise produce a Gemini-generated answer signed
+/// as if the requested provider had answered it.
+fn select_backend(
+    state: &Arc<AppState>,
+    provider: Option<&str>,
+) -> Result<Box<dyn TransformerBackend>, EnclaveError> {
+    match provider {
+        None | Some("gemini") => Ok(Box::new(GeminiBackend { state: state.clone() })),
+        Some("openai") => Ok(Box::new(OpenAiBackend { state: state.clone() })),
+        Some("anthropic") => Ok(Box::new(AnthropicBackend { state: state.clone() })),
+        Some(other) => Err(EnclaveError::GenericError(format!(
+            "Unknown provider '{}'; expected one of gemini, openai, anthropic",
+            other
+        ))),
+    }
 }
 
 pub async fn process_gemini_query(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ProcessDataRequest<GeminiRequest>>,
 ) -> Result<Json<ProcessedDataResponse<IntentMessage<GeminiResponse>>>, EnclaveError> {
+    // Base64 inflates size by ~4/3, so reject an oversized payload by its
+    // encoded length *before* decoding — checking the decoded length instead
+    // would mean the full buffer is already allocated by the time the limit
+    // triggers, defeating the point of the guard.
+    let max_bytes = max_file_bytes();
+    let max_encoded_len = max_bytes.div_ceil(3) * 4;
+    if request.payload.file_content.len() > max_encoded_len {
+        return Err(EnclaveError::GenericError(format!(
+            "Encoded file is {} bytes, which exceeds the allowance for a {} byte limit",
+            request.payload.file_content.len(),
+            max_bytes
+        )));
+    }
+
     // Decode the base64 file content
     let file_bytes = general_purpose::STANDARD.decode(&request.payload.file_content)
         .map_err(|e| EnclaveError::GenericError(format!("Failed to decode file content: {}", e)))?;
-    
+
+    if file_bytes.len() > max_bytes {
+        return Err(EnclaveError::GenericError(format!(
+            "File is {} bytes, which exceeds the {} byte limit",
+            file_bytes.len(),
+            max_bytes
+        )));
+    }
+
     let mut hasher = Sha256::new();
     hasher.update(&file_bytes);
     let file_hash = hasher.finalize().to_vec();
 
-    // Prepare Gemini API request
-    let model = "gemini-1.5-flash"; // or "gemini-1.5-pro"
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-        model, state.gemini_api_key
-    );
-
-    let prompt = format!(
-        "Analyze the following {} file and answer this question: {}\n\nFile content:\n{}\n\nIMPORTANT: Provide a clear, concise answer without using any special characters, markdown formatting, asterisks, dollar signs, or newlines. Use only plain text with spaces.",
-        request.payload.file_type,
-        request.payload.question,
-        String::from_utf8_lossy(&file_bytes)
-    );
-
-    let body = serde_json::json!({
-        "contents": [{
-            "parts": [{
-                "text": prompt
-            }]
-        }],
-        "generationConfig": {
-            "temperature": 0.7,
-            "maxOutputTokens": 2048,
+    // Commit to the exact context (system instruction, prior turns, file
+    // type, new question) that produced the answer. Each field is prefixed
+    // with a presence flag and/or its length so distinct transcripts can't
+    // hash to the same digest.
+    let mut transcript_hasher = Sha256::new();
+    match &request.payload.system_instruction {
+        Some(system_instruction) => {
+            transcript_hasher.update([1u8]);
+            hash_field(&mut transcript_hasher, system_instruction.as_bytes());
         }
-    });
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| EnclaveError::GenericError(format!("Failed to call Gemini API: {}", e)))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(EnclaveError::GenericError(format!("Gemini API error: {}", error_text)));
+        None => transcript_hasher.update([0u8]),
     }
+    for turn in request.payload.history.as_deref().unwrap_or(&[]) {
+        hash_field(&mut transcript_hasher, turn.role.as_bytes());
+        hash_field(&mut transcript_hasher, turn.text.as_bytes());
+    }
+    hash_field(&mut transcript_hasher, request.payload.file_type.as_bytes());
+    hash_field(&mut transcript_hasher, request.payload.question.as_bytes());
+    let transcript_hash = transcript_hasher.finalize().to_vec();
 
-    let json = response.json::<Value>().await
-        .map_err(|e| EnclaveError::GenericError(format!("Failed to parse Gemini response: {}", e)))?;
+    state.rate_limiter.try_acquire().await?;
 
-    let raw_answer = json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .unwrap_or("No response generated");
+    let backend_request = BackendRequest {
+        question: request.payload.question.clone(),
+        file_content: request.payload.file_content.clone(),
+        file_type: request.payload.file_type.clone(),
+        history: request.payload.history.clone().unwrap_or_default(),
+        system_instruction: request.payload.system_instruction.clone(),
+        model: request.payload.model.clone(),
+        generation: effective_generation_params(&request.payload),
+    };
+    let backend = select_backend(&state, request.payload.provider.as_deref())?;
+    let backend_answer = backend.generate(&backend_request).await?;
 
     // Clean the answer to make it blockchain-friendly
-    let clean_answer = raw_answer
+    let clean_answer = backend_answer.answer
         .replace('\n', " ")          // Replace newlines with spaces
         .replace('\r', " ")          // Replace carriage returns
         .replace('\t', " ")          // Replace tabs with spaces
@@ -124,10 +230,12 @@ pub async fn process_gemini_query(
         GeminiResponse {
             question: clean_question,
             answer: clean_answer,
-            model: model.to_string(),
+            model: backend_answer.model,
             file_hash,
+            transcript_hash,
+            generation_config: backend_answer.generation_config,
         },
         current_timestamp,
         IntentScope::Gemini,
     )))
-} 
\ No newline at end of file
+}
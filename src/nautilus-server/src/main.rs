@@ -2,19 +2,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use axum::extract::DefaultBodyLimit;
 use axum::{routing::get, routing::post, Router};
 use fastcrypto::{ed25519::Ed25519KeyPair, traits::KeyPair};
 use nautilus_server::app::process_data;
 use nautilus_server::common::{get_attestation, health_check};
 use nautilus_server::AppState;
 use nautilus_server::gemini::process_gemini_query;
+use nautilus_server::rate_limiter::RateLimiter;
+use nautilus_server::vertex_auth::{VertexAuthenticator, VertexConfig};
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
 mod app;
+mod backend;
+mod backends;
 mod common;
 mod gemini;
+mod rate_limiter;
+mod vertex_auth;
+
+const DEFAULT_MAX_REQUESTS_PER_SECOND: u32 = 10;
+/// Ceiling on the whole request body axum will buffer before handlers ever
+/// run, so an oversized `/process_gemini` payload is rejected before any
+/// handler-level size check (e.g. `gemini::max_file_bytes`) gets a chance to
+/// allocate anything. Comfortably above the default base64-encoded file
+/// budget to leave room for the rest of the JSON payload.
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 32 * 1024 * 1024;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -23,11 +38,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Fetch API keys from environment variables (set by secrets.json)
     let api_key = std::env::var("weatherApiKey").expect("weatherApiKey must be set");
     let gemini_api_key = std::env::var("geminiApiKey").expect("geminiApiKey must be set");
+    // OpenAI and Anthropic backends are opt-in: the `provider` field on a
+    // request selects them, but they only work if the matching key is set.
+    let openai_api_key = std::env::var("openaiApiKey").ok();
+    let anthropic_api_key = std::env::var("anthropicApiKey").ok();
+
+    // Vertex AI is opt-in: only provisioned when the enclave's secrets.json
+    // supplies a service-account ADC file, in which case requests are signed
+    // with a short-lived OAuth2 token instead of the Gemini API key above.
+    let vertex = std::env::var("vertexAdcPath").ok().map(|adc_path| {
+        let project = std::env::var("vertexProject").expect("vertexProject must be set when vertexAdcPath is set");
+        let location = std::env::var("vertexLocation").unwrap_or_else(|_| "us-central1".to_string());
+        VertexAuthenticator::from_adc_file(&adc_path, VertexConfig { project, location })
+            .expect("Failed to load Vertex AI service account credentials")
+    });
+
+    // Guards the outbound Gemini/OpenAI/Anthropic calls so a caller can't
+    // burn the operator's upstream quota through an unthrottled route.
+    let max_requests_per_second: u32 = std::env::var("maxRequestsPerSecond")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_REQUESTS_PER_SECOND);
+    let rate_limiter = RateLimiter::spawn(max_requests_per_second);
+
+    let max_request_body_bytes: usize = std::env::var("maxRequestBodyBytes")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_REQUEST_BODY_BYTES);
 
-    let state = Arc::new(AppState { 
-        eph_kp, 
-        api_key, 
-        gemini_api_key 
+    let state = Arc::new(AppState {
+        eph_kp,
+        api_key,
+        gemini_api_key,
+        openai_api_key,
+        anthropic_api_key,
+        vertex,
+        rate_limiter,
     });
 
     // Define your own restricted CORS policy here if needed.
@@ -40,6 +86,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/health_check", get(health_check))
         .route("/process_gemini", post(process_gemini_query))
         .with_state(state)
+        .layer(DefaultBodyLimit::max(max_request_body_bytes))
         .layer(cors);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
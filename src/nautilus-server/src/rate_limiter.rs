@@ -0,0 +1,64 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A token-bucket limiter guarding outbound LLM calls. The enclave holds the
+//! only copy of the upstream API key, so an unthrottled `/process_gemini`
+//! route would let any caller burn the operator's quota.
+
+use crate::EnclaveError;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time;
+
+/// Holds up to `requests_per_second` tokens (starting full) and refills one
+/// token every `1 / requests_per_second` seconds via a background task, so
+/// after an initial burst of at most that many requests, steady-state
+/// throughput matches the configured rate.
+pub struct RateLimiter {
+    capacity: u32,
+    tokens: Mutex<u32>,
+}
+
+impl RateLimiter {
+    /// Spawns the background refill task and returns a handle shared across
+    /// `AppState` clones.
+    pub fn spawn(requests_per_second: u32) -> Arc<Self> {
+        let capacity = requests_per_second.max(1);
+        let limiter = Arc::new(Self {
+            capacity,
+            tokens: Mutex::new(capacity),
+        });
+
+        let refill_target = limiter.clone();
+        let refill_interval = Duration::from_secs_f64(1.0 / capacity as f64);
+        tokio::spawn(async move {
+            let mut ticker = time::interval(refill_interval);
+            loop {
+                ticker.tick().await;
+                let mut tokens = refill_target.tokens.lock().await;
+                if *tokens < refill_target.capacity {
+                    *tokens += 1;
+                }
+            }
+        });
+
+        limiter
+    }
+
+    /// Takes one token if available, or reports a rate-limit error. Callers
+    /// need to tell this apart from a server fault, so it's a dedicated
+    /// `EnclaveError::RateLimited` variant (mapped to HTTP 429) rather than
+    /// the catch-all `GenericError` (mapped to 500).
+    pub async fn try_acquire(&self) -> Result<(), EnclaveError> {
+        let mut tokens = self.tokens.lock().await;
+        if *tokens > 0 {
+            *tokens -= 1;
+            Ok(())
+        } else {
+            Err(EnclaveError::RateLimited(
+                "Rate limit exceeded, try again shortly".to_string(),
+            ))
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c), Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! OAuth2 access-token minting for Vertex AI from a service-account
+//! Application Default Credentials (ADC) JSON file, used in place of a
+//! static Gemini API key when the enclave is deployed against Vertex.
+
+use crate::EnclaveError;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// Lifetime of the JWT assertion we sign, not the OAuth access token itself
+/// (whose actual lifetime comes back as `expires_in` in the token response).
+const ASSERTION_LIFETIME_SECS: u64 = 3600;
+/// Refresh the cached token once it's within this many seconds of expiring.
+const REFRESH_SKEW_SECS: u64 = 60;
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// Which GCP project/location Vertex AI requests should target.
+#[derive(Debug, Clone)]
+pub struct VertexConfig {
+    pub project: String,
+    pub location: String,
+}
+
+/// Minimal subset of a GCP service-account ADC JSON file.
+#[derive(Debug, Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+struct JwtClaims {
+    iss: String,
+    scope: String,
+    aud: String,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: u64,
+}
+
+/// Mints and caches short-lived Vertex AI bearer tokens, refreshing them
+/// from the service-account private key only once the cached token is near
+/// expiry.
+pub struct VertexAuthenticator {
+    pub config: VertexConfig,
+    key: ServiceAccountKey,
+    cached: RwLock<Option<CachedToken>>,
+}
+
+impl VertexAuthenticator {
+    /// Loads a service-account ADC JSON file from disk and pairs it with the
+    /// target Vertex AI project/location.
+    pub fn from_adc_file(path: &str, config: VertexConfig) -> Result<Self, EnclaveError> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to read ADC file: {}", e)))?;
+        let key: ServiceAccountKey = serde_json::from_str(&raw)
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to parse ADC file: {}", e)))?;
+        Ok(Self {
+            config,
+            key,
+            cached: RwLock::new(None),
+        })
+    }
+
+    /// Returns a valid bearer token, minting a new one if none is cached or
+    /// the cached one is within `REFRESH_SKEW_SECS` of the expiry Google
+    /// actually reported for it.
+    pub async fn access_token(&self) -> Result<String, EnclaveError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to get timestamp: {}", e)))?
+            .as_secs();
+
+        if let Some(cached) = self.cached.read().await.as_ref() {
+            if cached.expires_at > now + REFRESH_SKEW_SECS {
+                return Ok(cached.access_token.clone());
+            }
+        }
+
+        let (access_token, expires_in) = self.fetch_token(now).await?;
+        *self.cached.write().await = Some(CachedToken {
+            access_token: access_token.clone(),
+            expires_at: now + expires_in,
+        });
+        Ok(access_token)
+    }
+
+    async fn fetch_token(&self, now: u64) -> Result<(String, u64), EnclaveError> {
+        let claims = JwtClaims {
+            iss: self.key.client_email.clone(),
+            scope: CLOUD_PLATFORM_SCOPE.to_string(),
+            aud: self.key.token_uri.clone(),
+            iat: now,
+            exp: now + ASSERTION_LIFETIME_SECS,
+        };
+
+        let encoding_key = EncodingKey::from_rsa_pem(self.key.private_key.as_bytes())
+            .map_err(|e| EnclaveError::GenericError(format!("Invalid service account private key: {}", e)))?;
+        let assertion = encode(&Header::new(Algorithm::RS256), &claims, &encoding_key)
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to sign JWT assertion: {}", e)))?;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&self.key.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to exchange JWT for access token: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EnclaveError::GenericError(format!("Token exchange failed: {}", error_text)));
+        }
+
+        let parsed = response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| EnclaveError::GenericError(format!("Failed to parse token response: {}", e)))?;
+        Ok((parsed.access_token, parsed.expires_in))
+    }
+}